@@ -1,30 +1,118 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use codec::{Decode, Encode};
-use frame_support::sp_runtime::{traits::AccountIdConversion, ModuleId};
+use frame_support::sp_runtime::{
+    traits::AccountIdConversion, FixedU128, ModuleId, Perbill, Permill,
+};
 use frame_support::traits::Imbalance;
-use frame_support::traits::{Currency, ExistenceRequirement, ReservableCurrency};
+use frame_support::traits::{
+    Currency, EnsureOrigin, ExistenceRequirement, LockIdentifier, LockableCurrency,
+    ReservableCurrency, WithdrawReasons,
+};
+use frame_support::weights::Weight;
 use frame_support::{
     decl_error, decl_event, decl_module, decl_storage, dispatch, ensure, traits::Get,
 };
 use frame_system::ensure_signed;
 use glass_pumpkin::prime;
 use num_bigint::BigUint;
+use orml_traits::{MultiCurrency, MultiReservableCurrency};
+use sp_io::hashing::blake2_256;
+use sp_runtime::traits::{Saturating, Zero};
+use sp_std::prelude::*;
 
 /// Module Id of our pallet
 /// It's used to get the pallet's treasury pool
 const PALLET_ID: ModuleId = ModuleId(*b"Treasury");
 
+/// An index used to identify a treasury spending proposal
+pub type ProposalIndex = u32;
+
+/// Identifier of a registered asset. `NATIVE_ASSET_ID` is reserved for the pallet's
+/// native `Currency` and is never looked up in `ConversionRateToNative`.
+pub type AssetId = u32;
+
+/// Asset id used to fund a problem with the native `Currency` instead of a registered asset
+pub const NATIVE_ASSET_ID: AssetId = 0;
+
+/// Lock identifier used on the linear vesting of resolver rewards
+const REWARD_LOCK_ID: LockIdentifier = *b"mangatvs";
+
 /// Make the trait of our pallet
 pub trait Trait: frame_system::Trait {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
-    type Currency: Currency<Self::AccountId> + ReservableCurrency<Self::AccountId>;
+    type Currency: Currency<Self::AccountId>
+        + ReservableCurrency<Self::AccountId>
+        + LockableCurrency<Self::AccountId, Moment = Self::BlockNumber>;
+
+    /// Other assets problems can be funded in. Each needs an entry in
+    /// `ConversionRateToNative`, but the rate's value is not read: rewards in these
+    /// assets are reserved and paid out in the asset itself, never converted to native
+    type MultiCurrency: MultiCurrency<Self::AccountId, CurrencyId = AssetId, Balance = BalanceOf<Self>>
+        + MultiReservableCurrency<Self::AccountId>;
+
+    /// Origin allowed to approve treasury spending proposals
+    type ApproveOrigin: EnsureOrigin<Self::Origin>;
+
+    /// Origin allowed to reject treasury spending proposals
+    type RejectOrigin: EnsureOrigin<Self::Origin>;
+
+    /// Origin allowed to register asset conversion rates
+    type CreateOrigin: EnsureOrigin<Self::Origin>;
+
+    /// Fraction of a proposal's value that must be bonded by the proposer
+    type ProposalBond: Get<Permill>;
+
+    /// Minimum amount of the bond required, regardless of `ProposalBond`
+    type ProposalBondMinimum: Get<BalanceOf<Self>>;
+
+    /// Period, in blocks, between successive spends out of the treasury pool
+    type SpendPeriod: Get<Self::BlockNumber>;
+
+    /// Bond reserved on an account for as long as one of its solution commitments is outstanding
+    type CommitBond: Get<BalanceOf<Self>>;
+
+    /// Minimum number of blocks that must elapse between a commit and its reveal, so the
+    /// commitment is finalized before the factors it hides become public
+    type RevealDelay: Get<Self::BlockNumber>;
+
+    /// Number of blocks after which an un-revealed commitment is considered stale and can be
+    /// cleared to free its bond
+    type CommitExpiry: Get<Self::BlockNumber>;
+
+    /// Bond reserved for each `reveal_solution` attempt, to make spamming wrong answers costly
+    type ResolveBond: Get<BalanceOf<Self>>;
+
+    /// Fraction of `ResolveBond` slashed into the treasury pool on a wrong answer
+    type SlashFraction: Get<Permill>;
+
+    /// When set, a resolver's reward is locked and linearly released over this many
+    /// blocks instead of being immediately spendable. Only applies to rewards paid in
+    /// the native currency: `LockableCurrency` has no multi-asset equivalent wired into
+    /// this pallet, so resolver payouts for asset-funded problems are never locked.
+    type VestingBlocks: Get<Option<Self::BlockNumber>>;
 }
 
+/// A blake2-256 commitment to a solution, as produced off-chain by a solver
+pub type CommitmentHash = [u8; 32];
+
 /// Balance on our pallet
 type BalanceOf<T> =
     <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
 
+/// A treasury spending proposal, awaiting approval or rejection
+#[derive(Debug, Encode, Decode, PartialEq, Eq, Clone)]
+pub struct Proposal<AccountId, Balance> {
+    /// The account proposing the spend
+    proposer: AccountId,
+    /// The amount to be spent
+    value: Balance,
+    /// The account to whom the payment is made, should the proposal be approved
+    beneficiary: AccountId,
+    /// The bond reserved on the proposer's account
+    bond: Balance,
+}
+
 /// A Number, it is the number to find a solution for
 pub type Number = u128;
 
@@ -33,8 +121,12 @@ pub type Number = u128;
 pub struct Problem<T: Trait> {
     /// The number to resolve
     number: Number,
-    /// Amount of reward
+    /// Amount of reward, denominated in `asset_id`
     reward: BalanceOf<T>,
+    /// The asset the reward was funded in (`NATIVE_ASSET_ID` for the native `Currency`)
+    asset_id: AssetId,
+    /// Block after which, if still unresolved, the submitter can reclaim the reward
+    deadline: Option<T::BlockNumber>,
     /// The solution to the problem (when resolved)
     solution: Option<(u128, u128)>,
     /// Account id of the submitter
@@ -47,6 +139,30 @@ decl_storage! {
     trait Store for Module<T: Trait> as MangataModule {
         /// Map of all the problems submitted
         ProblemsMap: map hasher(identity) Number => Option<Problem<T>> = None;
+
+        /// Number of proposals that have been made, used as the next proposal index
+        ProposalCount get(fn proposal_count): ProposalIndex;
+        /// Spending proposals, awaiting approval or rejection
+        Proposals get(fn proposals):
+            map hasher(twox_64_concat) ProposalIndex => Option<Proposal<T::AccountId, BalanceOf<T>>>;
+        /// Proposals that have been approved and are queued for payout
+        Approvals get(fn approvals): Vec<ProposalIndex>;
+
+        /// Conversion rate from a registered asset to the native currency, set by governance
+        ConversionRateToNative get(fn conversion_rate_to_native):
+            map hasher(twox_64_concat) AssetId => Option<FixedU128>;
+
+        /// Outstanding solution commitments, keyed by the problem and the committer.
+        /// Double-mapped so `reveal_solution` can enumerate every commit still
+        /// outstanding on a problem to enforce "earliest commit wins ties".
+        Commitments get(fn commitments):
+            double_map hasher(twox_64_concat) Number, hasher(blake2_128_concat) T::AccountId
+            => Option<(CommitmentHash, T::BlockNumber)>;
+
+        /// Per-account schedule of vesting reward chunks, as (amount, block at which it
+        /// is fully unlocked) pairs
+        RewardLocks get(fn reward_locks):
+            map hasher(blake2_128_concat) T::AccountId => Vec<(BalanceOf<T>, T::BlockNumber)>;
     }
 }
 
@@ -62,6 +178,36 @@ decl_event!(
         /// A problem was resolved
         /// [number, a, b, who]
         ProblemResolved(u128, u128, u128, AccountId),
+        /// New spending proposal
+        /// [proposal_index]
+        Proposed(ProposalIndex),
+        /// A proposal was rejected, its bond slashed into the pool
+        /// [proposal_index, slashed_bond]
+        Rejected(ProposalIndex, Balance),
+        /// We have ended a spend period and will now allocate funds
+        /// [budget_remaining]
+        Spending(Balance),
+        /// Some funds have been allocated to a beneficiary
+        /// [proposal_index, amount, beneficiary]
+        Awarded(ProposalIndex, Balance, AccountId),
+        /// Some of the pool has been burnt
+        /// [amount]
+        Burnt(Balance),
+        /// A conversion rate to the native currency was set for an asset
+        /// [asset_id, rate]
+        ConversionRateSet(AssetId, FixedU128),
+        /// A solution commitment was registered for a problem
+        /// [number, who]
+        SolutionCommitted(u128, AccountId),
+        /// A stale commitment was cleared and its bond freed
+        /// [number, who]
+        CommitmentExpired(u128, AccountId),
+        /// A problem's deadline passed unsolved and its reward was reclaimed by the submitter
+        /// [number, submitter]
+        ProblemExpired(u128, AccountId),
+        /// A resolve bond was slashed for a wrong answer
+        /// [number, who, amount]
+        SolutionSlashed(u128, AccountId, Balance),
     }
 );
 
@@ -70,12 +216,31 @@ decl_error! {
     pub enum Error for Module<T: Trait> {
         /// Trying to resolve a problem that doesn't exist
         InexistentNumber,
-        /// Wrong answer to a problem
-        WrongAnswer,
         /// Problem was already resolved
         AlreadyResolved,
         /// Problem was already submitted
         AlreadySubmitted,
+        /// No proposal at that index
+        InvalidProposalIndex,
+        /// Proposer's balance is too low to reserve the proposal bond
+        InsufficientProposersBalance,
+        /// No conversion rate to native currency is registered for that asset
+        UnknownAsset,
+        /// This account already has an outstanding commitment for that problem
+        AlreadyCommitted,
+        /// No commitment was found for that problem and account
+        NoCommitment,
+        /// Not enough blocks have elapsed since the commit for it to be revealed yet
+        RevealTooEarly,
+        /// The revealed factors and salt don't hash to the stored commitment
+        CommitmentMismatch,
+        /// The commitment hasn't reached `CommitExpiry` yet, so it can't be cleared
+        CommitmentNotExpired,
+        /// The problem has no deadline, or it hasn't passed yet
+        DeadlineNotReached,
+        /// Someone else has an earlier, still-outstanding commitment on this problem;
+        /// they get to reveal first, or have their commitment expire, before this one can
+        NotEarliestCommit,
     }
 }
 
@@ -91,6 +256,162 @@ impl<T: Trait> Module<T> {
     fn account_id() -> T::AccountId {
         PALLET_ID.into_account()
     }
+
+    /// The spendable amount of the pool: its free balance less the existential deposit,
+    /// which stays behind so the pool account is never reaped
+    fn pot() -> BalanceOf<T> {
+        T::Currency::free_balance(&Self::account_id())
+            .saturating_sub(T::Currency::minimum_balance())
+    }
+
+    /// Calculate the bond required for a proposal of the given value
+    fn calculate_bond(value: BalanceOf<T>) -> BalanceOf<T> {
+        T::ProposalBondMinimum::get().max(T::ProposalBond::get() * value)
+    }
+
+    /// Hash the factors `a, b` of `number` together with the committer and a salt, as a
+    /// commitment that can be revealed later without exposing the factors up front
+    fn commitment_hash(
+        number: Number,
+        a: u128,
+        b: u128,
+        who: &T::AccountId,
+        salt: u128,
+    ) -> CommitmentHash {
+        let mut preimage = number.encode();
+        preimage.extend(a.encode());
+        preimage.extend(b.encode());
+        preimage.extend(who.encode());
+        preimage.extend(salt.encode());
+
+        blake2_256(&preimage)
+    }
+
+    /// Slash `SlashFraction` of `ResolveBond` from `who` into the treasury pool, returning
+    /// the rest
+    fn slash_resolve_bond(number: Number, who: &T::AccountId) {
+        let slashed = T::SlashFraction::get() * T::ResolveBond::get();
+
+        let (imbalance, _) = T::Currency::slash_reserved(who, slashed);
+        T::Currency::resolve_creating(&Self::account_id(), imbalance);
+        T::Currency::unreserve(who, T::ResolveBond::get() - slashed);
+
+        Self::deposit_event(RawEvent::SolutionSlashed(number, who.clone(), slashed));
+    }
+
+    /// Queue `amount` to vest linearly over `vesting_blocks`, then refresh the account's lock
+    fn add_vesting_lock(who: &T::AccountId, amount: BalanceOf<T>, vesting_blocks: T::BlockNumber) {
+        let unlock_at = frame_system::Module::<T>::block_number() + vesting_blocks;
+
+        RewardLocks::<T>::mutate(who, |schedule| schedule.push((amount, unlock_at)));
+
+        Self::update_lock(who);
+    }
+
+    /// Recompute how much of `who`'s reward schedule is still locked and refresh the
+    /// `LockableCurrency` lock accordingly, dropping any chunk that's fully vested.
+    ///
+    /// Runs even when vesting is disabled (`VestingBlocks` is `None` or `0`), so that a
+    /// lock set up while vesting was still enabled gets released rather than stuck forever.
+    fn update_lock(who: &T::AccountId) {
+        let now = frame_system::Module::<T>::block_number();
+
+        let locked = match T::VestingBlocks::get() {
+            Some(vesting_blocks) if !vesting_blocks.is_zero() => {
+                let schedule: Vec<_> = RewardLocks::<T>::get(who)
+                    .into_iter()
+                    .filter(|(_, unlock_at)| *unlock_at > now)
+                    .collect();
+
+                let locked =
+                    schedule
+                        .iter()
+                        .fold(Zero::zero(), |acc: BalanceOf<T>, (amount, unlock_at)| {
+                            let remaining = *unlock_at - now;
+                            acc + Permill::from_rational_approximation(remaining, vesting_blocks)
+                                * *amount
+                        });
+
+                if locked.is_zero() {
+                    RewardLocks::<T>::remove(who);
+                } else {
+                    RewardLocks::<T>::insert(who, schedule);
+                }
+
+                locked
+            }
+            // Vesting disabled: nothing stays locked, but any leftover schedule from
+            // when it was enabled must still be cleared out.
+            _ => {
+                RewardLocks::<T>::remove(who);
+                Zero::zero()
+            }
+        };
+
+        if locked.is_zero() {
+            T::Currency::remove_lock(REWARD_LOCK_ID, who);
+        } else {
+            // Mirror the standard vesting lock: exclude transaction-fee payment so a
+            // resolver whose whole free balance is a freshly-locked reward can still pay
+            // fees to submit `unlock_vested` once a chunk of it vests.
+            T::Currency::set_lock(
+                REWARD_LOCK_ID,
+                who,
+                locked,
+                WithdrawReasons::except(WithdrawReasons::TRANSACTION_PAYMENT),
+            );
+        }
+    }
+
+    /// Spend some money from the pool on the approved proposals that currently fit the budget,
+    /// leaving the rest queued for a later spend period
+    fn spend_funds() -> Weight {
+        let mut budget_remaining = Self::pot();
+        Self::deposit_event(RawEvent::Spending(budget_remaining));
+
+        let mut remaining_approvals = Vec::new();
+
+        for index in Approvals::get().into_iter() {
+            if let Some(p) = Proposals::<T>::get(index) {
+                if p.value <= budget_remaining {
+                    let payout = T::Currency::transfer(
+                        &Self::account_id(),
+                        &p.beneficiary,
+                        p.value,
+                        ExistenceRequirement::KeepAlive,
+                    );
+
+                    match payout {
+                        Ok(()) => {
+                            budget_remaining -= p.value;
+                            Proposals::<T>::remove(index);
+                            T::Currency::unreserve(&p.proposer, p.bond);
+
+                            Self::deposit_event(RawEvent::Awarded(index, p.value, p.beneficiary));
+                        }
+                        Err(_) => {
+                            // Payout failed (e.g. the beneficiary's balance would overflow);
+                            // leave the proposal queued for a future spend period instead of
+                            // reporting it as awarded.
+                            remaining_approvals.push(index);
+                        }
+                    }
+                } else {
+                    // Can't afford it this period, keep it queued
+                    remaining_approvals.push(index);
+                }
+            }
+        }
+
+        Approvals::put(remaining_approvals);
+
+        // No burn rate is configured yet, so nothing is actually destroyed; the
+        // event still fires so downstream tooling can rely on it being present
+        // every spend period.
+        Self::deposit_event(RawEvent::Burnt(Zero::zero()));
+
+        T::DbWeight::get().reads_writes(2, 2)
+    }
 }
 
 decl_module! {
@@ -101,22 +422,133 @@ decl_module! {
         // Events must be initialized if they are used by the pallet.
         fn deposit_event() = default;
 
-        /// Submit a problem
+        /// Every `SpendPeriod` blocks, pay out the approved proposals that fit the
+        /// current pool balance, keeping the rest queued for the next period.
+        fn on_initialize(n: T::BlockNumber) -> Weight {
+            if (n % T::SpendPeriod::get()).is_zero() {
+                Self::spend_funds()
+            } else {
+                0
+            }
+        }
+
+        /// Propose that the treasury pool pay `value` to `beneficiary`
+        ///
+        /// A deposit is reserved from the proposer, equal to `ProposalBond`
+        /// percent of the proposed value, with a minimum of `ProposalBondMinimum`.
+        ///
+        /// 1 read & 2 writes to the db
+        #[weight = T::DbWeight::get().reads_writes(1, 2) + 10_000]
+        pub fn propose_spend(origin, value: BalanceOf<T>, beneficiary: T::AccountId) -> dispatch::DispatchResult {
+            let proposer = ensure_signed(origin)?;
+
+            let bond = Self::calculate_bond(value);
+            T::Currency::reserve(&proposer, bond)
+                .map_err(|_| Error::<T>::InsufficientProposersBalance)?;
+
+            let index = Self::proposal_count();
+            ProposalCount::put(index + 1);
+            Proposals::<T>::insert(index, Proposal {
+                proposer,
+                value,
+                beneficiary,
+                bond,
+            });
+
+            Self::deposit_event(RawEvent::Proposed(index));
+
+            Ok(())
+        }
+
+        /// Reject a proposed spend, slashing its bond into the treasury pool
+        ///
+        /// Must be called from `RejectOrigin`
+        ///
+        /// 1 read & 2 writes to the db
+        #[weight = T::DbWeight::get().reads_writes(1, 2) + 10_000]
+        pub fn reject_proposal(origin, #[compact] proposal_id: ProposalIndex) -> dispatch::DispatchResult {
+            T::RejectOrigin::ensure_origin(origin)?;
+
+            let proposal = Proposals::<T>::take(proposal_id).ok_or(Error::<T>::InvalidProposalIndex)?;
+            let bond = proposal.bond;
+            let (imbalance, _) = T::Currency::slash_reserved(&proposal.proposer, bond);
+            T::Currency::resolve_creating(&Self::account_id(), imbalance);
+
+            Self::deposit_event(RawEvent::Rejected(proposal_id, bond));
+
+            Ok(())
+        }
+
+        /// Approve a proposed spend, queueing it to be paid out of the pool at the
+        /// next `SpendPeriod`
+        ///
+        /// Must be called from `ApproveOrigin`
+        ///
+        /// 1 read & 1 write to the db
+        #[weight = T::DbWeight::get().reads_writes(1, 1) + 10_000]
+        pub fn approve_proposal(origin, #[compact] proposal_id: ProposalIndex) -> dispatch::DispatchResult {
+            T::ApproveOrigin::ensure_origin(origin)?;
+
+            ensure!(Proposals::<T>::contains_key(proposal_id), Error::<T>::InvalidProposalIndex);
+
+            Approvals::append(proposal_id);
+
+            Ok(())
+        }
+
+        /// Register, or update, the conversion rate from `asset_id` to the native currency
+        ///
+        /// Must be called from `CreateOrigin`
+        ///
+        /// 0 read & 1 write to the db
+        #[weight = T::DbWeight::get().reads_writes(0, 1) + 10_000]
+        pub fn set_conversion_rate(origin, asset_id: AssetId, rate: FixedU128) -> dispatch::DispatchResult {
+            T::CreateOrigin::ensure_origin(origin)?;
+
+            ConversionRateToNative::insert(asset_id, rate);
+
+            Self::deposit_event(RawEvent::ConversionRateSet(asset_id, rate));
+
+            Ok(())
+        }
+
+        /// Submit a problem, funding its reward in `asset_id`
+        /// (`NATIVE_ASSET_ID` for the native currency)
+        ///
         /// 1 read & 3 writes to the db
         #[weight = T::DbWeight::get().reads_writes(1, 3) + 10_000]
-        pub fn submit_problem(origin, number: u128, reward: BalanceOf<T>) -> dispatch::DispatchResult {
+        pub fn submit_problem(origin, number: u128, asset_id: AssetId, reward: BalanceOf<T>, deadline: Option<T::BlockNumber>) -> dispatch::DispatchResult {
             let who = ensure_signed(origin)?;
 
             // Check if the problem was already submitted
             ensure!(!ProblemsMap::<T>::contains_key(number), Error::<T>::AlreadySubmitted);
 
-            // Reserve the reward amount on the account
-            T::Currency::reserve(&who, reward)?;
+            // Asset-funded problems must reference a registered conversion rate. Only the
+            // presence of the rate is checked, its value is not read here or elsewhere; the
+            // resolver and treasury payouts in `reveal_solution` stay denominated in the
+            // funding asset instead of being converted through it, since this pallet has
+            // no real market to swap through and converting to native would mean minting
+            // native currency with nothing backing it.
+            if asset_id != NATIVE_ASSET_ID {
+                ensure!(
+                    ConversionRateToNative::contains_key(asset_id),
+                    Error::<T>::UnknownAsset
+                );
+            }
+
+            // Reserve the reward amount on the account, in the funding asset
+            if asset_id == NATIVE_ASSET_ID {
+                T::Currency::reserve(&who, reward)?;
+            } else {
+                T::MultiCurrency::reserve(asset_id, &who, reward)?;
+            }
 
             // Insert new problem to resolve
             ProblemsMap::<T>::insert(number, Problem {
                 number,
                 reward,
+                asset_id,
+                deadline,
                 resolver: None,
                 solution: None,
                 submitter: who.clone(),
@@ -129,12 +561,64 @@ decl_module! {
             Ok(())
         }
 
-        /// Resolve a problem
-        /// 1 read & 5 write to the db
-        #[weight = T::DbWeight::get().reads_writes(1, 5) + 10_000]
-        pub fn resolve_problem(origin, number: u128, a: u128, b: u128) -> dispatch::DispatchResult {
+        /// Commit to a solution for a problem, without revealing the factors yet
+        ///
+        /// `commitment` must equal `blake2_256(number ++ a ++ b ++ who ++ salt)`. Reserves
+        /// `CommitBond` for as long as the commitment is outstanding.
+        ///
+        /// 2 reads & 2 writes to the db
+        #[weight = T::DbWeight::get().reads_writes(2, 2) + 10_000]
+        pub fn commit_solution(origin, number: u128, commitment: CommitmentHash) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(!Commitments::<T>::contains_key(number, &who), Error::<T>::AlreadyCommitted);
+
+            let problem = ProblemsMap::<T>::get(number).ok_or(Error::<T>::InexistentNumber)?;
+            ensure!(problem.resolver.is_none(), Error::<T>::AlreadyResolved);
+
+            T::Currency::reserve(&who, T::CommitBond::get())?;
+
+            let now = frame_system::Module::<T>::block_number();
+            Commitments::<T>::insert(number, &who, (commitment, now));
+
+            Self::deposit_event(RawEvent::SolutionCommitted(number, who));
+
+            Ok(())
+        }
+
+        /// Reveal a previously committed solution and, if correct, resolve the problem
+        ///
+        /// Can only be called at least `RevealDelay` blocks after the matching
+        /// `commit_solution`, so the commitment is finalized before the factors leak.
+        /// Reserves `ResolveBond` for the attempt, slashing `SlashFraction` of it into
+        /// the treasury pool if the revealed factors turn out to be wrong.
+        ///
+        /// 3 reads & 6 write to the db
+        #[weight = T::DbWeight::get().reads_writes(3, 6) + 10_000]
+        pub fn reveal_solution(origin, number: u128, a: u128, b: u128, salt: u128) -> dispatch::DispatchResult {
             let who = ensure_signed(origin)?;
 
+            let (commitment, committed_at) = Commitments::<T>::get(number, &who)
+                .ok_or(Error::<T>::NoCommitment)?;
+
+            let now = frame_system::Module::<T>::block_number();
+            ensure!(now >= committed_at + T::RevealDelay::get(), Error::<T>::RevealTooEarly);
+            ensure!(
+                Self::commitment_hash(number, a, b, &who, salt) == commitment,
+                Error::<T>::CommitmentMismatch
+            );
+
+            // Earliest valid commit wins ties: if someone else still has an outstanding
+            // commitment on this problem from an earlier block, they get to reveal first.
+            // They'll eventually lose their slot to `expire_commitment` if they never do.
+            let earlier_commit_outstanding = Commitments::<T>::iter_prefix(number)
+                .any(|(other, (_, other_committed_at))| other != who && other_committed_at < committed_at);
+            ensure!(!earlier_commit_outstanding, Error::<T>::NotEarliestCommit);
+
+            // The commitment has been honestly revealed either way; release it and its bond
+            Commitments::<T>::remove(number, &who);
+            T::Currency::unreserve(&who, T::CommitBond::get());
+
             let problem = match ProblemsMap::<T>::get(number) {
                 None => {
                     return Err(Error::<T>::InexistentNumber.into())
@@ -145,37 +629,79 @@ decl_module! {
                 Some(problem) => problem,
             };
 
-            let multiplied = match a.checked_mul(b) {
-                Some(n) => n,
-                _ => return Err(Error::<T>::WrongAnswer.into())
+            // Reserve a bond for this resolve attempt, so spamming wrong answers is costly
+            T::Currency::reserve(&who, T::ResolveBond::get())?;
+
+            let correct = match a.checked_mul(b) {
+                Some(n) => n == number && is_prime(a) && is_prime(b),
+                None => false,
             };
 
-            // Check if the solution is correct
-            if multiplied != number || !is_prime(a) || !is_prime(b) {
-                return Err(Error::<T>::WrongAnswer.into());
+            if !correct {
+                // Slash the resolve bond and stop here. This returns `Ok(())` rather than
+                // an error: the slash must be durable, and riding it on an `Err` return
+                // only works because this FRAME version doesn't roll back storage on a
+                // failed dispatchable. A `#[transactional]` call, or a future runtime that
+                // wraps every extrinsic that way, would silently undo the slash while the
+                // caller still paid fees for it.
+                Self::slash_resolve_bond(number, &who);
+                return Ok(());
             }
 
-            // Unreserve the reward
-            T::Currency::unreserve(&problem.submitter, problem.reward);
+            // Solution is correct, give the resolve bond back
+            T::Currency::unreserve(&who, T::ResolveBond::get());
+
+            if problem.asset_id == NATIVE_ASSET_ID {
+                // Unreserve the reward
+                T::Currency::unreserve(&problem.submitter, problem.reward);
+
+                // Make a 80/20 ratio of the reward
+                let imbalance = T::Currency::burn(problem.reward);
+                let (to_resolver, to_treasury) = imbalance.ration(80, 20);
 
-            // Make a 80/20 ratio of the reward
-            let imbalance = T::Currency::burn(problem.reward);
-            let (to_resolver, to_treasury) = imbalance.ration(80, 20);
+                // Transfer 80% to the resolver
+                T::Currency::transfer(&problem.submitter, &who, to_resolver.peek(), ExistenceRequirement::KeepAlive)?;
 
-            // Transfer 80% to the resolver
-            T::Currency::transfer(&problem.submitter, &who, to_resolver.peek(), ExistenceRequirement::KeepAlive)?;
+                // If vesting is configured, lock the reward and release it linearly
+                // instead of leaving it immediately spendable
+                if let Some(vesting_blocks) = T::VestingBlocks::get() {
+                    Self::add_vesting_lock(&who, to_resolver.peek(), vesting_blocks);
+                }
 
-            // Transfer 20% to pallet treasury
-            T::Currency::transfer(&problem.submitter, &Self::account_id(), to_treasury.peek(), ExistenceRequirement::KeepAlive)?;
+                // Transfer 20% to pallet treasury
+                T::Currency::transfer(&problem.submitter, &Self::account_id(), to_treasury.peek(), ExistenceRequirement::KeepAlive)?;
+            } else {
+                // Unreserve the reward, still denominated in the funding asset
+                T::MultiCurrency::unreserve(problem.asset_id, &problem.submitter, problem.reward);
+
+                // Make a 80/20 ratio of the reward, in the funding asset
+                let to_resolver = Perbill::from_percent(80) * problem.reward;
+                let to_treasury = problem.reward - to_resolver;
+
+                // Pay 80% to the resolver, in the funding asset. Unlike the native path,
+                // this is never vesting-locked: `VestingBlocks` rides on `LockableCurrency`,
+                // which only covers `T::Currency`, so there's no lock to apply here.
+                T::MultiCurrency::transfer(problem.asset_id, &problem.submitter, &who, to_resolver)?;
+
+                // Pay the 20% treasury cut into the pool, still denominated in the funding
+                // asset. We only ever hold a conversion *rate*, and never read it, not
+                // a real market to swap through, so minting native currency against it would
+                // inflate total issuance with nothing backing it.
+                //
+                // Known gap: `pot`/`spend_funds`/`propose_spend` only ever move `T::Currency`,
+                // the native balance. Asset-denominated cuts paid here just accumulate on
+                // `account_id()` in that asset with no spend path out of it yet. Draining them
+                // would need either a per-asset treasury (mirroring the native proposal flow)
+                // or a real swap back to native; until one of those lands, asset treasury
+                // balances are stuck exactly like native ones were before proposals existed.
+                T::MultiCurrency::transfer(problem.asset_id, &problem.submitter, &Self::account_id(), to_treasury)?;
+            }
 
             // Set the problem as resolved
-            ProblemsMap::<T>::mutate(number, |_| {
-                Problem::<T> {
-                    number,
-                    reward: problem.reward,
-                    solution: Some((a, b)),
-                    resolver: Some(who.clone()),
-                    submitter: problem.submitter,
+            ProblemsMap::<T>::mutate(number, |stored| {
+                if let Some(stored) = stored {
+                    stored.solution = Some((a, b));
+                    stored.resolver = Some(who.clone());
                 }
             });
 
@@ -185,23 +711,74 @@ decl_module! {
             // Return a successful DispatchResult
             Ok(())
         }
-    }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::is_prime;
-
-    // First few prime numbers
-    const PRIMES: &[u128] = &[
-        2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89,
-        97,
-    ];
-
-    #[test]
-    fn test_prime() {
-        for n in 0..100 {
-            assert_eq!(is_prime(n), PRIMES.contains(&n));
+        /// Clear a commitment that was never revealed within `CommitExpiry` blocks, freeing
+        /// its bond back to the committer. Callable by anyone, since a stale commitment is
+        /// public and useless to keep around.
+        ///
+        /// 1 read & 2 writes to the db
+        #[weight = T::DbWeight::get().reads_writes(1, 2) + 10_000]
+        pub fn expire_commitment(origin, number: u128, who: T::AccountId) -> dispatch::DispatchResult {
+            ensure_signed(origin)?;
+
+            let (_, committed_at) = Commitments::<T>::get(number, &who)
+                .ok_or(Error::<T>::NoCommitment)?;
+
+            let now = frame_system::Module::<T>::block_number();
+            ensure!(now >= committed_at + T::CommitExpiry::get(), Error::<T>::CommitmentNotExpired);
+
+            Commitments::<T>::remove(number, &who);
+            T::Currency::unreserve(&who, T::CommitBond::get());
+
+            Self::deposit_event(RawEvent::CommitmentExpired(number, who));
+
+            Ok(())
+        }
+
+        /// Reclaim the reward of a problem that passed its deadline without being solved
+        ///
+        /// 1 read & 1 write to the db
+        #[weight = T::DbWeight::get().reads_writes(1, 1) + 10_000]
+        pub fn reclaim_problem(origin, number: u128) -> dispatch::DispatchResult {
+            ensure_signed(origin)?;
+
+            let problem = ProblemsMap::<T>::get(number).ok_or(Error::<T>::InexistentNumber)?;
+            ensure!(problem.resolver.is_none(), Error::<T>::AlreadyResolved);
+
+            let now = frame_system::Module::<T>::block_number();
+            let past_deadline = problem.deadline.map_or(false, |deadline| now > deadline);
+            ensure!(past_deadline, Error::<T>::DeadlineNotReached);
+
+            // Return the reward to the submitter, in the asset it was funded in
+            if problem.asset_id == NATIVE_ASSET_ID {
+                T::Currency::unreserve(&problem.submitter, problem.reward);
+            } else {
+                T::MultiCurrency::unreserve(problem.asset_id, &problem.submitter, problem.reward);
+            }
+
+            ProblemsMap::<T>::remove(number);
+
+            Self::deposit_event(RawEvent::ProblemExpired(number, problem.submitter));
+
+            Ok(())
+        }
+
+        /// Recompute the caller's vesting schedule, releasing any reward chunk that has
+        /// fully vested and shrinking the lock to whatever remains
+        ///
+        /// 1 read & 2 writes to the db
+        #[weight = T::DbWeight::get().reads_writes(1, 2) + 10_000]
+        pub fn unlock_vested(origin) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            Self::update_lock(&who);
+
+            Ok(())
         }
     }
 }
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,314 @@
+//! Mock runtime used to exercise the pallet's dispatchables in `tests.rs`
+
+use crate::{self as mangata, AssetId};
+use frame_support::{
+    impl_outer_event, impl_outer_origin, parameter_types, traits::OnInitialize, weights::Weight,
+};
+use frame_system as system;
+use orml_traits::{BalanceStatus, MultiCurrency, MultiReservableCurrency};
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, IdentityLookup},
+    DispatchError, DispatchResult, Perbill, Permill,
+};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+impl_outer_origin! {
+    pub enum Origin for Test {}
+}
+
+impl_outer_event! {
+    pub enum TestEvent for Test {
+        system<T>,
+        pallet_balances<T>,
+        mangata<T>,
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Test;
+
+pub type AccountId = u64;
+pub type Balance = u128;
+pub type BlockNumber = u64;
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const MaximumBlockWeight: Weight = 1024;
+    pub const MaximumBlockLength: u32 = 2 * 1024;
+    pub const AvailableBlockRatio: Perbill = Perbill::one();
+}
+
+impl system::Trait for Test {
+    type BaseCallFilter = ();
+    type Origin = Origin;
+    type Call = ();
+    type Index = u64;
+    type BlockNumber = BlockNumber;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = AccountId;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = TestEvent;
+    type BlockHashCount = BlockHashCount;
+    type MaximumBlockWeight = MaximumBlockWeight;
+    type DbWeight = ();
+    type BlockExecutionWeight = ();
+    type ExtrinsicBaseWeight = ();
+    type MaximumExtrinsicWeight = MaximumBlockWeight;
+    type MaximumBlockLength = MaximumBlockLength;
+    type AvailableBlockRatio = AvailableBlockRatio;
+    type Version = ();
+    type PalletInfo = ();
+    type AccountData = pallet_balances::AccountData<Balance>;
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+}
+
+parameter_types! {
+    pub const ExistentialDeposit: Balance = 1;
+}
+
+impl pallet_balances::Trait for Test {
+    type Balance = Balance;
+    type DustRemoval = ();
+    type Event = TestEvent;
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = system::Module<Test>;
+    type WeightInfo = ();
+    type MaxLocks = ();
+}
+
+/// A bare-bones `MultiCurrency` double, backed by an in-memory map of
+/// (free, reserved) balances per (account, asset). Good enough to exercise
+/// the pallet's multi-asset paths without pulling in a full token pallet.
+pub struct MockMultiCurrency;
+
+thread_local! {
+    static ASSET_BALANCES: RefCell<HashMap<(AccountId, AssetId), (Balance, Balance)>> =
+        RefCell::new(HashMap::new());
+}
+
+impl MockMultiCurrency {
+    fn get(who: &AccountId, id: AssetId) -> (Balance, Balance) {
+        ASSET_BALANCES.with(|b| b.borrow().get(&(*who, id)).copied().unwrap_or((0, 0)))
+    }
+
+    fn set(who: &AccountId, id: AssetId, value: (Balance, Balance)) {
+        ASSET_BALANCES.with(|b| b.borrow_mut().insert((*who, id), value));
+    }
+
+    /// Test helper to credit an account's free balance of an asset
+    pub fn set_balance(who: AccountId, id: AssetId, free: Balance) {
+        Self::set(&who, id, (free, 0));
+    }
+}
+
+impl MultiCurrency<AccountId> for MockMultiCurrency {
+    type CurrencyId = AssetId;
+    type Balance = Balance;
+
+    fn minimum_balance(_currency_id: Self::CurrencyId) -> Self::Balance {
+        0
+    }
+
+    fn total_issuance(_currency_id: Self::CurrencyId) -> Self::Balance {
+        0
+    }
+
+    fn total_balance(currency_id: Self::CurrencyId, who: &AccountId) -> Self::Balance {
+        let (free, reserved) = Self::get(who, currency_id);
+        free + reserved
+    }
+
+    fn free_balance(currency_id: Self::CurrencyId, who: &AccountId) -> Self::Balance {
+        Self::get(who, currency_id).0
+    }
+
+    fn ensure_can_withdraw(
+        currency_id: Self::CurrencyId,
+        who: &AccountId,
+        amount: Self::Balance,
+    ) -> DispatchResult {
+        if Self::free_balance(currency_id, who) >= amount {
+            Ok(())
+        } else {
+            Err(DispatchError::Other("insufficient balance"))
+        }
+    }
+
+    fn transfer(
+        currency_id: Self::CurrencyId,
+        from: &AccountId,
+        to: &AccountId,
+        amount: Self::Balance,
+    ) -> DispatchResult {
+        Self::withdraw(currency_id, from, amount)?;
+        Self::deposit(currency_id, to, amount)
+    }
+
+    fn deposit(
+        currency_id: Self::CurrencyId,
+        who: &AccountId,
+        amount: Self::Balance,
+    ) -> DispatchResult {
+        let (free, reserved) = Self::get(who, currency_id);
+        Self::set(who, currency_id, (free + amount, reserved));
+        Ok(())
+    }
+
+    fn withdraw(
+        currency_id: Self::CurrencyId,
+        who: &AccountId,
+        amount: Self::Balance,
+    ) -> DispatchResult {
+        let (free, reserved) = Self::get(who, currency_id);
+        if free < amount {
+            return Err(DispatchError::Other("insufficient balance"));
+        }
+        Self::set(who, currency_id, (free - amount, reserved));
+        Ok(())
+    }
+
+    fn can_slash(currency_id: Self::CurrencyId, who: &AccountId, amount: Self::Balance) -> bool {
+        Self::free_balance(currency_id, who) >= amount
+    }
+
+    fn slash(
+        currency_id: Self::CurrencyId,
+        who: &AccountId,
+        amount: Self::Balance,
+    ) -> Self::Balance {
+        let (free, reserved) = Self::get(who, currency_id);
+        let slashed = free.min(amount);
+        Self::set(who, currency_id, (free - slashed, reserved));
+        amount - slashed
+    }
+}
+
+impl MultiReservableCurrency<AccountId> for MockMultiCurrency {
+    fn can_reserve(currency_id: Self::CurrencyId, who: &AccountId, value: Self::Balance) -> bool {
+        Self::free_balance(currency_id, who) >= value
+    }
+
+    fn slash_reserved(
+        currency_id: Self::CurrencyId,
+        who: &AccountId,
+        value: Self::Balance,
+    ) -> Self::Balance {
+        let (free, reserved) = Self::get(who, currency_id);
+        let slashed = reserved.min(value);
+        Self::set(who, currency_id, (free, reserved - slashed));
+        value - slashed
+    }
+
+    fn reserved_balance(currency_id: Self::CurrencyId, who: &AccountId) -> Self::Balance {
+        Self::get(who, currency_id).1
+    }
+
+    fn reserve(
+        currency_id: Self::CurrencyId,
+        who: &AccountId,
+        value: Self::Balance,
+    ) -> DispatchResult {
+        let (free, reserved) = Self::get(who, currency_id);
+        if free < value {
+            return Err(DispatchError::Other("insufficient balance"));
+        }
+        Self::set(who, currency_id, (free - value, reserved + value));
+        Ok(())
+    }
+
+    fn unreserve(
+        currency_id: Self::CurrencyId,
+        who: &AccountId,
+        value: Self::Balance,
+    ) -> Self::Balance {
+        let (free, reserved) = Self::get(who, currency_id);
+        let returned = reserved.min(value);
+        Self::set(who, currency_id, (free + returned, reserved - returned));
+        value - returned
+    }
+
+    fn repatriate_reserved(
+        currency_id: Self::CurrencyId,
+        slashed: &AccountId,
+        beneficiary: &AccountId,
+        value: Self::Balance,
+        _status: BalanceStatus,
+    ) -> Result<Self::Balance, DispatchError> {
+        let (_, reserved) = Self::get(slashed, currency_id);
+        let moved = reserved.min(value);
+        Self::slash_reserved(currency_id, slashed, moved);
+        Self::deposit(currency_id, beneficiary, moved)?;
+        Ok(value - moved)
+    }
+}
+
+parameter_types! {
+    pub const ProposalBond: Permill = Permill::from_percent(5);
+    pub const ProposalBondMinimum: Balance = 10;
+    pub const SpendPeriod: BlockNumber = 10;
+    pub const CommitBond: Balance = 5;
+    pub const RevealDelay: BlockNumber = 2;
+    pub const CommitExpiry: BlockNumber = 20;
+    pub const ResolveBond: Balance = 5;
+    pub const SlashFraction: Permill = Permill::from_percent(50);
+    pub const VestingBlocks: Option<BlockNumber> = Some(10);
+}
+
+impl mangata::Trait for Test {
+    type Event = TestEvent;
+    type Currency = pallet_balances::Module<Test>;
+    type MultiCurrency = MockMultiCurrency;
+    type ApproveOrigin = frame_system::EnsureRoot<AccountId>;
+    type RejectOrigin = frame_system::EnsureRoot<AccountId>;
+    type CreateOrigin = frame_system::EnsureRoot<AccountId>;
+    type ProposalBond = ProposalBond;
+    type ProposalBondMinimum = ProposalBondMinimum;
+    type SpendPeriod = SpendPeriod;
+    type CommitBond = CommitBond;
+    type RevealDelay = RevealDelay;
+    type CommitExpiry = CommitExpiry;
+    type ResolveBond = ResolveBond;
+    type SlashFraction = SlashFraction;
+    type VestingBlocks = VestingBlocks;
+}
+
+pub type System = system::Module<Test>;
+pub type Balances = pallet_balances::Module<Test>;
+pub type Mangata = mangata::Module<Test>;
+
+pub const ALICE: AccountId = 1;
+pub const BOB: AccountId = 2;
+pub const CHARLIE: AccountId = 3;
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut storage = system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap();
+
+    pallet_balances::GenesisConfig::<Test> {
+        balances: vec![(ALICE, 1_000), (BOB, 1_000), (CHARLIE, 1_000)],
+    }
+    .assimilate_storage(&mut storage)
+    .unwrap();
+
+    storage.into()
+}
+
+pub fn run_to_block(n: BlockNumber) {
+    while System::block_number() < n {
+        Mangata::on_initialize(System::block_number() + 1);
+        System::set_block_number(System::block_number() + 1);
+    }
+}
+
+/// The most recently emitted event, for asserting on what a call deposited
+pub fn last_event() -> TestEvent {
+    System::events().pop().expect("an event was emitted").event
+}
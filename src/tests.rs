@@ -0,0 +1,589 @@
+use crate::{
+    is_prime, mock::*, Commitments, Error, ProblemsMap, RawEvent, NATIVE_ASSET_ID, REWARD_LOCK_ID,
+};
+use frame_support::{
+    assert_err, assert_noop, assert_ok,
+    traits::{Currency, ReservableCurrency},
+};
+use orml_traits::{MultiCurrency, MultiReservableCurrency};
+use sp_runtime::{FixedPointNumber, FixedU128};
+
+const ASSET: u32 = 7;
+
+// First few prime numbers
+const PRIMES: &[u128] = &[
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+];
+
+#[test]
+fn test_prime() {
+    for n in 0..100 {
+        assert_eq!(is_prime(n), PRIMES.contains(&n));
+    }
+}
+
+#[test]
+fn propose_spend_reserves_bond() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Mangata::propose_spend(Origin::signed(ALICE), 100, BOB));
+
+        // 5% of 100 is below ProposalBondMinimum (10), so the minimum applies
+        assert_eq!(Balances::reserved_balance(ALICE), 10);
+        assert_eq!(Mangata::proposal_count(), 1);
+        assert!(Mangata::proposals(0).is_some());
+        assert_eq!(last_event(), TestEvent::mangata(RawEvent::Proposed(0)));
+    });
+}
+
+#[test]
+fn propose_spend_fails_without_enough_balance() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Mangata::propose_spend(Origin::signed(ALICE), 1_000_000, BOB),
+            Error::<Test>::InsufficientProposersBalance
+        );
+    });
+}
+
+#[test]
+fn reject_proposal_slashes_bond_into_pool() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Mangata::propose_spend(Origin::signed(ALICE), 100, BOB));
+        let pool_before = Balances::free_balance(Mangata::account_id());
+
+        assert_ok!(Mangata::reject_proposal(Origin::root(), 0));
+
+        assert_eq!(Balances::reserved_balance(ALICE), 0);
+        assert!(Mangata::proposals(0).is_none());
+        assert_eq!(
+            Balances::free_balance(Mangata::account_id()),
+            pool_before + 10
+        );
+        assert_eq!(last_event(), TestEvent::mangata(RawEvent::Rejected(0, 10)));
+    });
+}
+
+#[test]
+fn reject_proposal_fails_for_unknown_index() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Mangata::reject_proposal(Origin::root(), 0),
+            Error::<Test>::InvalidProposalIndex
+        );
+    });
+}
+
+#[test]
+fn approve_and_spend_pays_beneficiary() {
+    new_test_ext().execute_with(|| {
+        // Fund the pool so the proposal can be paid out
+        assert_ok!(Balances::transfer(
+            Origin::signed(ALICE),
+            Mangata::account_id(),
+            500
+        ));
+
+        assert_ok!(Mangata::propose_spend(Origin::signed(BOB), 100, CHARLIE));
+        assert_ok!(Mangata::approve_proposal(Origin::root(), 0));
+
+        let charlie_before = Balances::free_balance(CHARLIE);
+        run_to_block(SpendPeriod::get());
+
+        assert_eq!(Balances::free_balance(CHARLIE), charlie_before + 100);
+        assert_eq!(Balances::reserved_balance(BOB), 0);
+        assert!(Mangata::proposals(0).is_none());
+    });
+}
+
+#[test]
+fn spend_funds_keeps_proposal_over_budget_queued() {
+    new_test_ext().execute_with(|| {
+        // The pool has no funds, so even a tiny proposal can't fit this period
+        assert_ok!(Mangata::propose_spend(Origin::signed(ALICE), 1, BOB));
+        assert_ok!(Mangata::approve_proposal(Origin::root(), 0));
+
+        run_to_block(SpendPeriod::get());
+
+        assert!(Mangata::proposals(0).is_some());
+        assert_eq!(Balances::reserved_balance(ALICE), 10);
+    });
+}
+
+#[test]
+fn spend_funds_keeps_proposal_queued_when_payout_transfer_fails() {
+    new_test_ext().execute_with(|| {
+        // Fund the pool generously so the proposal fits the budget
+        assert_ok!(Balances::transfer(
+            Origin::signed(ALICE),
+            Mangata::account_id(),
+            500
+        ));
+
+        // Push CHARLIE's balance right to the edge, so crediting it with the 100 payout
+        // would overflow and the payout transfer itself fails, independent of budget
+        let headroom = Balance::MAX - Balances::free_balance(CHARLIE) - 1;
+        let _ = Balances::deposit_creating(&CHARLIE, headroom);
+
+        assert_ok!(Mangata::propose_spend(Origin::signed(ALICE), 100, CHARLIE));
+        assert_ok!(Mangata::approve_proposal(Origin::root(), 0));
+
+        run_to_block(SpendPeriod::get());
+
+        // The transfer overflowed and failed; the proposal must stay queued and the
+        // proposer's bond must stay reserved rather than being reported as Awarded
+        assert!(Mangata::proposals(0).is_some());
+        assert_eq!(Balances::reserved_balance(ALICE), 10);
+    });
+}
+
+#[test]
+fn submit_problem_fails_for_unregistered_asset() {
+    new_test_ext().execute_with(|| {
+        MockMultiCurrency::set_balance(ALICE, ASSET, 1_000);
+
+        assert_noop!(
+            Mangata::submit_problem(Origin::signed(ALICE), 15, ASSET, 100, None),
+            Error::<Test>::UnknownAsset
+        );
+    });
+}
+
+#[test]
+fn submit_problem_reserves_reward_in_the_funding_asset() {
+    new_test_ext().execute_with(|| {
+        MockMultiCurrency::set_balance(ALICE, ASSET, 1_000);
+        assert_ok!(Mangata::set_conversion_rate(
+            Origin::root(),
+            ASSET,
+            FixedU128::saturating_from_integer(2)
+        ));
+
+        assert_ok!(Mangata::submit_problem(
+            Origin::signed(ALICE),
+            15,
+            ASSET,
+            100,
+            None
+        ));
+
+        assert_eq!(MockMultiCurrency::reserved_balance(ASSET, &ALICE), 100);
+        assert_eq!(MockMultiCurrency::free_balance(ASSET, &ALICE), 900);
+    });
+}
+
+#[test]
+fn reveal_solution_pays_resolver_and_treasury_in_the_funding_asset() {
+    new_test_ext().execute_with(|| {
+        MockMultiCurrency::set_balance(ALICE, ASSET, 1_000);
+        assert_ok!(Mangata::set_conversion_rate(
+            Origin::root(),
+            ASSET,
+            FixedU128::saturating_from_integer(2)
+        ));
+        assert_ok!(Mangata::submit_problem(
+            Origin::signed(ALICE),
+            15,
+            ASSET,
+            100,
+            None
+        ));
+
+        let commitment = Mangata::commitment_hash(15, 3, 5, &BOB, 42);
+        assert_ok!(Mangata::commit_solution(
+            Origin::signed(BOB),
+            15,
+            commitment
+        ));
+
+        run_to_block(RevealDelay::get());
+        assert_ok!(Mangata::reveal_solution(Origin::signed(BOB), 15, 3, 5, 42));
+
+        // 80% of the reward goes to the resolver, 20% stays in the pool -- both still
+        // denominated in ASSET, never converted or minted into the native currency
+        assert_eq!(MockMultiCurrency::free_balance(ASSET, &BOB), 80);
+        assert_eq!(
+            MockMultiCurrency::free_balance(ASSET, &Mangata::account_id()),
+            20
+        );
+        assert_eq!(MockMultiCurrency::reserved_balance(ASSET, &ALICE), 0);
+
+        let problem = ProblemsMap::<Test>::get(15).unwrap();
+        assert_eq!(problem.resolver, Some(BOB));
+        assert_eq!(problem.solution, Some((3, 5)));
+    });
+}
+
+#[test]
+fn commit_solution_fails_if_already_committed() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Mangata::submit_problem(
+            Origin::signed(ALICE),
+            15,
+            NATIVE_ASSET_ID,
+            100,
+            None
+        ));
+
+        let commitment = Mangata::commitment_hash(15, 3, 5, &BOB, 42);
+        assert_ok!(Mangata::commit_solution(
+            Origin::signed(BOB),
+            15,
+            commitment
+        ));
+
+        assert_noop!(
+            Mangata::commit_solution(Origin::signed(BOB), 15, commitment),
+            Error::<Test>::AlreadyCommitted
+        );
+    });
+}
+
+#[test]
+fn reveal_fails_before_reveal_delay_has_elapsed() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Mangata::submit_problem(
+            Origin::signed(ALICE),
+            15,
+            NATIVE_ASSET_ID,
+            100,
+            None
+        ));
+
+        let commitment = Mangata::commitment_hash(15, 3, 5, &BOB, 42);
+        assert_ok!(Mangata::commit_solution(
+            Origin::signed(BOB),
+            15,
+            commitment
+        ));
+
+        assert_noop!(
+            Mangata::reveal_solution(Origin::signed(BOB), 15, 3, 5, 42),
+            Error::<Test>::RevealTooEarly
+        );
+    });
+}
+
+#[test]
+fn reveal_fails_on_commitment_mismatch() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Mangata::submit_problem(
+            Origin::signed(ALICE),
+            15,
+            NATIVE_ASSET_ID,
+            100,
+            None
+        ));
+
+        let commitment = Mangata::commitment_hash(15, 3, 5, &BOB, 42);
+        assert_ok!(Mangata::commit_solution(
+            Origin::signed(BOB),
+            15,
+            commitment
+        ));
+        run_to_block(RevealDelay::get());
+
+        // Right factors, wrong salt: hashes to something else entirely
+        assert_noop!(
+            Mangata::reveal_solution(Origin::signed(BOB), 15, 3, 5, 43),
+            Error::<Test>::CommitmentMismatch
+        );
+    });
+}
+
+#[test]
+fn earliest_commit_wins_the_tie() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Mangata::submit_problem(
+            Origin::signed(ALICE),
+            15,
+            NATIVE_ASSET_ID,
+            100,
+            None
+        ));
+
+        // BOB commits first, at block 1
+        run_to_block(1);
+        let bob_commitment = Mangata::commitment_hash(15, 3, 5, &BOB, 1);
+        assert_ok!(Mangata::commit_solution(
+            Origin::signed(BOB),
+            15,
+            bob_commitment
+        ));
+
+        // CHARLIE commits a block later
+        run_to_block(2);
+        let charlie_commitment = Mangata::commitment_hash(15, 3, 5, &CHARLIE, 2);
+        assert_ok!(Mangata::commit_solution(
+            Origin::signed(CHARLIE),
+            15,
+            charlie_commitment
+        ));
+
+        // Both reveal delays have elapsed, but CHARLIE committed later than BOB, whose
+        // commitment is still outstanding -- CHARLIE doesn't get to jump the queue
+        run_to_block(2 + RevealDelay::get());
+        assert_noop!(
+            Mangata::reveal_solution(Origin::signed(CHARLIE), 15, 3, 5, 2),
+            Error::<Test>::NotEarliestCommit
+        );
+
+        // BOB, the earliest committer, can still reveal and wins
+        assert_ok!(Mangata::reveal_solution(Origin::signed(BOB), 15, 3, 5, 1));
+        assert_eq!(ProblemsMap::<Test>::get(15).unwrap().resolver, Some(BOB));
+
+        // Now that BOB's commitment is gone, CHARLIE's own reveal attempt no longer
+        // trips the tie-break, but the problem is already resolved. CHARLIE's
+        // commitment has still been honestly revealed, so it and its bond are released
+        assert_err!(
+            Mangata::reveal_solution(Origin::signed(CHARLIE), 15, 3, 5, 2),
+            Error::<Test>::AlreadyResolved
+        );
+        assert!(!Commitments::<Test>::contains_key(15, &CHARLIE));
+        assert_eq!(Balances::reserved_balance(CHARLIE), 0);
+    });
+}
+
+#[test]
+fn expire_commitment_frees_the_bond_once_stale() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Mangata::submit_problem(
+            Origin::signed(ALICE),
+            15,
+            NATIVE_ASSET_ID,
+            100,
+            None
+        ));
+
+        let commitment = Mangata::commitment_hash(15, 3, 5, &BOB, 42);
+        assert_ok!(Mangata::commit_solution(
+            Origin::signed(BOB),
+            15,
+            commitment
+        ));
+        assert_eq!(Balances::reserved_balance(BOB), CommitBond::get());
+
+        assert_noop!(
+            Mangata::expire_commitment(Origin::signed(ALICE), 15, BOB),
+            Error::<Test>::CommitmentNotExpired
+        );
+
+        run_to_block(CommitExpiry::get());
+        assert_ok!(Mangata::expire_commitment(Origin::signed(ALICE), 15, BOB));
+
+        assert_eq!(Balances::reserved_balance(BOB), 0);
+        assert!(!Commitments::<Test>::contains_key(15, &BOB));
+    });
+}
+
+#[test]
+fn reclaim_problem_fails_before_deadline() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Mangata::submit_problem(
+            Origin::signed(ALICE),
+            15,
+            NATIVE_ASSET_ID,
+            100,
+            Some(5)
+        ));
+
+        run_to_block(5);
+        assert_noop!(
+            Mangata::reclaim_problem(Origin::signed(ALICE), 15),
+            Error::<Test>::DeadlineNotReached
+        );
+    });
+}
+
+#[test]
+fn reclaim_problem_fails_without_a_deadline() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Mangata::submit_problem(
+            Origin::signed(ALICE),
+            15,
+            NATIVE_ASSET_ID,
+            100,
+            None
+        ));
+
+        run_to_block(1_000);
+        assert_noop!(
+            Mangata::reclaim_problem(Origin::signed(ALICE), 15),
+            Error::<Test>::DeadlineNotReached
+        );
+    });
+}
+
+#[test]
+fn reclaim_problem_refunds_the_submitter_once_past_deadline() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Mangata::submit_problem(
+            Origin::signed(ALICE),
+            15,
+            NATIVE_ASSET_ID,
+            100,
+            Some(5)
+        ));
+        assert_eq!(Balances::reserved_balance(ALICE), 100);
+
+        run_to_block(6);
+        assert_ok!(Mangata::reclaim_problem(Origin::signed(ALICE), 15));
+
+        assert_eq!(Balances::reserved_balance(ALICE), 0);
+        assert!(ProblemsMap::<Test>::get(15).is_none());
+        assert_eq!(
+            last_event(),
+            TestEvent::mangata(RawEvent::ProblemExpired(15, ALICE))
+        );
+    });
+}
+
+#[test]
+fn reclaim_problem_fails_once_already_resolved() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Mangata::submit_problem(
+            Origin::signed(ALICE),
+            15,
+            NATIVE_ASSET_ID,
+            100,
+            Some(5)
+        ));
+
+        let commitment = Mangata::commitment_hash(15, 3, 5, &BOB, 42);
+        assert_ok!(Mangata::commit_solution(
+            Origin::signed(BOB),
+            15,
+            commitment
+        ));
+        run_to_block(RevealDelay::get());
+        assert_ok!(Mangata::reveal_solution(Origin::signed(BOB), 15, 3, 5, 42));
+
+        run_to_block(6);
+        assert_noop!(
+            Mangata::reclaim_problem(Origin::signed(ALICE), 15),
+            Error::<Test>::AlreadyResolved
+        );
+    });
+}
+
+#[test]
+fn wrong_answer_slashes_the_resolve_bond_into_the_pool() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Mangata::submit_problem(
+            Origin::signed(ALICE),
+            15,
+            NATIVE_ASSET_ID,
+            100,
+            None
+        ));
+
+        // 4 * 4 = 16, not 15: a wrong but honestly-revealed answer
+        let commitment = Mangata::commitment_hash(15, 4, 4, &BOB, 42);
+        assert_ok!(Mangata::commit_solution(
+            Origin::signed(BOB),
+            15,
+            commitment
+        ));
+        run_to_block(RevealDelay::get());
+
+        let bob_reserved_before = Balances::reserved_balance(BOB);
+        let pool_before = Balances::free_balance(Mangata::account_id());
+
+        // A wrong-but-honest answer still slashes the resolve bond, but it's reported
+        // via `Ok(())` and the `SolutionSlashed` event, not an `Err` -- the slash must
+        // be durable, and this FRAME version only keeps it that way because storage
+        // isn't rolled back on a dispatch error, which `Err` would otherwise invite
+        assert_ok!(Mangata::reveal_solution(Origin::signed(BOB), 15, 4, 4, 42));
+
+        let slashed = SlashFraction::get() * ResolveBond::get();
+        assert_eq!(
+            Balances::reserved_balance(BOB),
+            bob_reserved_before - ResolveBond::get()
+        );
+        assert_eq!(
+            Balances::free_balance(Mangata::account_id()),
+            pool_before + slashed
+        );
+        assert_eq!(
+            last_event(),
+            TestEvent::mangata(RawEvent::SolutionSlashed(15, BOB, slashed))
+        );
+
+        // The problem is still unresolved and open for another attempt
+        assert!(ProblemsMap::<Test>::get(15).unwrap().resolver.is_none());
+    });
+}
+
+fn reward_lock_amount(who: &AccountId) -> Option<Balance> {
+    Balances::locks(who)
+        .into_iter()
+        .find(|lock| lock.id == REWARD_LOCK_ID)
+        .map(|lock| lock.amount)
+}
+
+#[test]
+fn reveal_solution_locks_the_native_reward_for_vesting() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Mangata::submit_problem(
+            Origin::signed(ALICE),
+            15,
+            NATIVE_ASSET_ID,
+            100,
+            None
+        ));
+
+        let commitment = Mangata::commitment_hash(15, 3, 5, &BOB, 42);
+        assert_ok!(Mangata::commit_solution(
+            Origin::signed(BOB),
+            15,
+            commitment
+        ));
+        run_to_block(RevealDelay::get());
+        assert_ok!(Mangata::reveal_solution(Origin::signed(BOB), 15, 3, 5, 42));
+
+        // BOB got the 80% resolver share (100 * 80%), and all of it starts out locked
+        assert_eq!(Balances::free_balance(BOB), 1_000 + 80);
+        assert_eq!(reward_lock_amount(&BOB), Some(80));
+
+        // Halfway through the vesting period, half the reward should still be locked
+        run_to_block(RevealDelay::get() + VestingBlocks::get().unwrap() / 2);
+        assert_ok!(Mangata::unlock_vested(Origin::signed(BOB)));
+        assert_eq!(reward_lock_amount(&BOB), Some(40));
+
+        // Once the vesting period has fully elapsed, the lock is dropped entirely
+        run_to_block(RevealDelay::get() + VestingBlocks::get().unwrap() + 1);
+        assert_ok!(Mangata::unlock_vested(Origin::signed(BOB)));
+        assert_eq!(reward_lock_amount(&BOB), None);
+    });
+}
+
+#[test]
+fn reveal_solution_never_locks_asset_funded_rewards() {
+    new_test_ext().execute_with(|| {
+        MockMultiCurrency::set_balance(ALICE, ASSET, 1_000);
+        assert_ok!(Mangata::set_conversion_rate(
+            Origin::root(),
+            ASSET,
+            FixedU128::saturating_from_integer(2)
+        ));
+        assert_ok!(Mangata::submit_problem(
+            Origin::signed(ALICE),
+            15,
+            ASSET,
+            100,
+            None
+        ));
+
+        let commitment = Mangata::commitment_hash(15, 3, 5, &BOB, 42);
+        assert_ok!(Mangata::commit_solution(
+            Origin::signed(BOB),
+            15,
+            commitment
+        ));
+        run_to_block(RevealDelay::get());
+        assert_ok!(Mangata::reveal_solution(Origin::signed(BOB), 15, 3, 5, 42));
+
+        // The reward was paid in ASSET, which `LockableCurrency` knows nothing about:
+        // there is no native-currency vesting lock to apply
+        assert_eq!(reward_lock_amount(&BOB), None);
+    });
+}